@@ -1,5 +1,10 @@
+pub mod reader;
+pub mod sinks;
+
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
@@ -9,25 +14,369 @@ use std::sync::Mutex;
 use chrono::SecondsFormat;
 use chrono::Utc;
 use codex_protocol::ConversationId;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::read::GzDecoder;
+use flate2::read::ZlibDecoder;
+use flate2::write::GzEncoder;
 use reqwest::StatusCode;
 use reqwest::header::HeaderMap;
+use serde::Deserialize;
 use serde_json::Value;
 use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
 use tracing::warn;
 
+use self::sinks::CompositeSink;
+use self::sinks::HttpBatchSink;
+use self::sinks::LogSink;
+
 pub const REQUEST_LOG_DIR_ENV: &str = "CODEX_REQUEST_LOG_DIR";
+pub const REQUEST_LOG_MAX_BYTES_ENV: &str = "CODEX_REQUEST_LOG_MAX_BYTES";
+pub const REQUEST_LOG_MAX_SEGMENTS_ENV: &str = "CODEX_REQUEST_LOG_MAX_SEGMENTS";
+
+/// Current shape of [`RequestLoggingConfig`] as loaded from the config file.
+/// Bumped whenever `log_rules` gains a field that changes how it's
+/// interpreted, so [`RequestLoggingConfig::validate`] can reject (or, in the
+/// future, migrate) shapes this binary predates.
+pub const CURRENT_REQUEST_LOG_CONFIG_VERSION: u32 = 1;
+
+/// Identifies one of the independent streams a `RequestLogger` can dispatch
+/// to. Kept small and explicit (rather than a free-text string) so the set of
+/// routable categories is discoverable from the type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogCategory {
+    /// Per-attempt `attempt-NNN-response.jsonl` stream (response-start, SSE
+    /// events, stream-closed).
+    Response,
+    /// `log_error`/`log_error_response` records, which a caller may want
+    /// aggregated into one conversation-wide `errors.jsonl` instead of
+    /// scattered across per-attempt files.
+    Error,
+}
+
+/// Per-category logging rule: whether the category is recorded at all, and
+/// an optional path that overrides its default destination.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LogRuleConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for LogRuleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+/// Ships every record of the JSONL streams (response and error) to a remote
+/// collector in addition to (not instead of) the local file sinks, so CI and
+/// headless runs can centralize captured request artifacts instead of
+/// leaving them on ephemeral disks. The per-attempt
+/// `attempt-NNN-request.json` file is written locally only.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RemoteSinkConfig {
+    /// Records are POSTed to `{base_url}/{conversation_id}/{attempt_id}`,
+    /// where `attempt_id` is `attempt-NNN` for the per-attempt response
+    /// stream or the literal `errors` for the shared error stream.
+    pub base_url: String,
+}
+
+/// Typed, versioned request-logging configuration loaded from the crate's
+/// config file, mirroring how other config sections separate out per-feature
+/// rules rather than relying on a single all-or-nothing toggle.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RequestLoggingConfig {
+    pub version: u32,
+    #[serde(default)]
+    pub log_rules: BTreeMap<LogCategory, LogRuleConfig>,
+    #[serde(default)]
+    pub remote: Option<RemoteSinkConfig>,
+}
+
+impl RequestLoggingConfig {
+    /// Rejects config versions newer than this binary understands instead of
+    /// guessing at an unknown shape. There is only one version today, so
+    /// there is nothing yet to migrate from.
+    pub fn validate(self) -> Option<Self> {
+        if self.version > CURRENT_REQUEST_LOG_CONFIG_VERSION {
+            warn!(
+                "request logging config version {} is newer than supported version {}; ignoring",
+                self.version, CURRENT_REQUEST_LOG_CONFIG_VERSION
+            );
+            return None;
+        }
+        Some(self)
+    }
+
+    fn rule(&self, category: LogCategory) -> LogRuleConfig {
+        self.log_rules.get(&category).cloned().unwrap_or_default()
+    }
+}
+
+/// Default rotation threshold for a single `attempt-NNN-response.jsonl` file: 10 MiB.
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Default number of rotated (and gzip-compressed) segments to retain per attempt.
+const DEFAULT_MAX_SEGMENTS: usize = 5;
+
+/// Marker written in place of a redacted header value or payload field.
+const REDACTED_MARKER: &str = "[REDACTED]";
+
+/// Header and JSON field names that are redacted from request/response logs by
+/// default. Matching is case-insensitive and, for JSON payloads, applies to a
+/// key at any depth so that auth material echoed back inside an error body is
+/// also caught, not just the top-level header map.
+fn default_redacted_names() -> HashSet<String> {
+    [
+        "authorization",
+        "proxy-authorization",
+        "cookie",
+        "set-cookie",
+        "openai-organization",
+        "openai-api-key",
+        "api-key",
+        "api_key",
+        "x-api-key",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
 
 fn timestamp() -> String {
     Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
 }
 
+/// Redaction rules applied to everything a `RequestLogger` writes to disk.
+#[derive(Clone, Debug)]
+pub struct RedactionPolicy {
+    /// Lower-cased header/JSON-key names to redact wherever they appear.
+    denied_names: HashSet<String>,
+    /// Extra JSON pointers (e.g. `/metadata/session_token`) to redact even
+    /// when the field name itself isn't in `denied_names`.
+    denied_pointers: HashSet<String>,
+    /// When set, redacted values are replaced with a salted SHA-256 prefix
+    /// instead of a flat `[REDACTED]` marker, so identical secrets can still
+    /// be correlated across log lines without being exposed.
+    hash_salt: Option<String>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self {
+            denied_names: default_redacted_names(),
+            denied_pointers: HashSet::new(),
+            hash_salt: None,
+        }
+    }
+}
+
+impl RedactionPolicy {
+    pub fn with_denied_pointers(mut self, pointers: impl IntoIterator<Item = String>) -> Self {
+        self.denied_pointers.extend(pointers);
+        self
+    }
+
+    pub fn with_hash_salt(mut self, salt: impl Into<String>) -> Self {
+        self.hash_salt = Some(salt.into());
+        self
+    }
+
+    fn is_denied_name(&self, name: &str) -> bool {
+        self.denied_names.contains(name.to_ascii_lowercase().as_str())
+    }
+
+    fn mask(&self, value: &str) -> Value {
+        match &self.hash_salt {
+            Some(salt) => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt.as_bytes());
+                hasher.update(value.as_bytes());
+                let digest = hasher.finalize();
+                let prefix: String = digest.iter().take(6).map(|b| format!("{b:02x}")).collect();
+                Value::String(format!("[REDACTED:{prefix}]"))
+            }
+            None => Value::String(REDACTED_MARKER.to_string()),
+        }
+    }
+
+    fn redact_headers(&self, headers: &mut BTreeMap<String, Vec<String>>) {
+        for (name, values) in headers.iter_mut() {
+            if self.is_denied_name(name) {
+                for value in values.iter_mut() {
+                    *value = match self.mask(value) {
+                        Value::String(s) => s,
+                        _ => REDACTED_MARKER.to_string(),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Returns a redacted clone of `value`: every object key matching
+    /// `denied_names` (at any depth) is masked, plus any explicit
+    /// `denied_pointers` that name a field by path rather than by key.
+    fn redact_value(&self, value: &Value) -> Value {
+        let mut out = value.clone();
+        self.redact_in_place(&mut out);
+        for pointer in &self.denied_pointers {
+            if let Some(slot) = out.pointer_mut(pointer) {
+                *slot = match slot.as_str() {
+                    Some(s) => self.mask(s),
+                    None => Value::String(REDACTED_MARKER.to_string()),
+                };
+            }
+        }
+        out
+    }
+
+    fn redact_in_place(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    if self.is_denied_name(key) {
+                        if let Some(s) = child.as_str() {
+                            *child = self.mask(s);
+                            continue;
+                        }
+                        *child = Value::String(REDACTED_MARKER.to_string());
+                        continue;
+                    }
+                    self.redact_in_place(child);
+                }
+            }
+            Value::Array(items) => {
+                for item in items.iter_mut() {
+                    self.redact_in_place(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Best-effort redaction of a raw response body: parsed as JSON and
+    /// redacted the same way as a request payload when possible (upstream
+    /// error bodies sometimes echo request headers back to the caller),
+    /// otherwise left untouched since it isn't structured.
+    fn redact_body(&self, body: &str) -> Value {
+        match serde_json::from_str::<Value>(body) {
+            Ok(parsed) => self.redact_value(&parsed),
+            Err(_) => Value::String(body.to_string()),
+        }
+    }
+}
+
+/// Stable failure taxonomy attached to every `error`/`error_response` record
+/// as a `class` field, so logs across many conversations can be aggregated
+/// by failure kind (rate-limit vs. transport vs. server) without regexing
+/// free-text messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    Timeout,
+    Connect,
+    Tls,
+    Decode,
+    RateLimited,
+    ServerError,
+    ClientError,
+    Unknown,
+}
+
+impl ErrorClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::Timeout => "Timeout",
+            ErrorClass::Connect => "Connect",
+            ErrorClass::Tls => "Tls",
+            ErrorClass::Decode => "Decode",
+            ErrorClass::RateLimited => "RateLimited",
+            ErrorClass::ServerError => "ServerError",
+            ErrorClass::ClientError => "ClientError",
+            ErrorClass::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Classifies an HTTP error response by status code alone.
+pub fn classify_status(status: StatusCode) -> ErrorClass {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        ErrorClass::RateLimited
+    } else if status.is_server_error() {
+        ErrorClass::ServerError
+    } else if status.is_client_error() {
+        ErrorClass::ClientError
+    } else {
+        ErrorClass::Unknown
+    }
+}
+
+/// Classifies a transport-level `reqwest::Error`. Timeout, connect, and
+/// decode are classified via `reqwest::Error`'s own `is_*` predicates rather
+/// than matching on `Display` text, so those mappings stay correct across
+/// reqwest's own message wording changes. TLS is the one exception: reqwest
+/// exposes no typed accessor for the TLS error it wraps (the concrete type
+/// varies with which TLS backend a build is compiled against), so
+/// `is_tls_error` falls back to text-matching the source chain and is
+/// correspondingly best-effort.
+pub fn classify_transport_error(err: &reqwest::Error) -> ErrorClass {
+    if err.is_timeout() {
+        ErrorClass::Timeout
+    } else if err.is_connect() {
+        ErrorClass::Connect
+    } else if err.is_decode() {
+        ErrorClass::Decode
+    } else if is_tls_error(err) {
+        ErrorClass::Tls
+    } else {
+        ErrorClass::Unknown
+    }
+}
+
+/// `reqwest::Error` has no `is_tls()` or other typed accessor for the TLS
+/// error it wraps, so this falls back to inspecting the error source chain's
+/// message text — best-effort, and the only classification in this module
+/// that relies on `Display` wording rather than a stable predicate.
+fn is_tls_error(err: &reqwest::Error) -> bool {
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if err.to_string().to_ascii_lowercase().contains("tls") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
 #[derive(Clone, Debug)]
 pub struct RequestAttemptLogger {
     inner: Arc<RequestAttemptLogInner>,
+    redaction: Arc<RedactionPolicy>,
 }
 
 impl RequestAttemptLogger {
     pub fn log_response_start(&self, status: StatusCode, headers: &HeaderMap) {
+        if let Some(encoding) = headers
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+        {
+            let mut guard = match self.inner.content_encoding.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            *guard = Some(encoding.trim().to_ascii_lowercase());
+        }
+
         let mut headers_map: BTreeMap<String, Vec<String>> = BTreeMap::new();
         for (name, value) in headers.iter() {
             headers_map
@@ -35,6 +384,7 @@ impl RequestAttemptLogger {
                 .or_default()
                 .push(value.to_str().unwrap_or_default().to_string());
         }
+        self.redaction.redact_headers(&mut headers_map);
 
         self.write_json_line(json!({
             "timestamp": timestamp(),
@@ -45,11 +395,18 @@ impl RequestAttemptLogger {
     }
 
     pub fn log_stream_event(&self, event: Option<&str>, data: &str) {
+        // SSE `data` fields are frequently JSON; embed a real nested object
+        // under `data_json` so captured logs are directly greppable instead
+        // of only holding an escaped string, while keeping `data` as the
+        // verbatim fallback for non-JSON payloads.
+        let data_json = serde_json::from_str::<Value>(data).ok();
+
         self.write_json_line(json!({
             "timestamp": timestamp(),
             "type": "sse_event",
             "event": event,
             "data": data,
+            "data_json": data_json,
         }));
     }
 
@@ -61,20 +418,56 @@ impl RequestAttemptLogger {
         }));
     }
 
+    /// For an error that isn't a `reqwest::Error` (e.g. one raised by this
+    /// crate's own request-building code). A failed HTTP call should go
+    /// through [`Self::log_transport_error`] instead, so its `class` reflects
+    /// the transport failure rather than always reading `"Unknown"`.
     pub fn log_error(&self, message: &str) {
-        self.write_json_line(json!({
+        self.write_error_line(json!({
             "timestamp": timestamp(),
             "type": "error",
+            "class": ErrorClass::Unknown.as_str(),
             "message": message,
         }));
     }
 
-    pub fn log_error_response(&self, status: StatusCode, body: &str) {
-        self.write_json_line(json!({
+    /// The logging counterpart to a failed `reqwest` call: callers that hold
+    /// a `reqwest::Error` from a request/connect/timeout failure should call
+    /// this instead of stringifying it into [`Self::log_error`], since this
+    /// carries enough structure (timeout/connect/decode flags) to classify
+    /// without resorting to message-string matching.
+    pub fn log_transport_error(&self, err: &reqwest::Error) {
+        self.write_error_line(json!({
+            "timestamp": timestamp(),
+            "type": "error",
+            "class": classify_transport_error(err).as_str(),
+            "message": err.to_string(),
+        }));
+    }
+
+    /// The logging counterpart to a non-2xx HTTP response. `body` must be the
+    /// *raw* response bytes exactly as received on the wire — still
+    /// `content-encoding`-compressed if the response was — since this
+    /// transparently inflates it (using the encoding observed in
+    /// [`Self::log_response_start`]) before redacting and writing it, so a
+    /// gzip/deflate/brotli-negotiated error body lands in the log as readable
+    /// JSON rather than unreadable bytes. An already-decoded body usually
+    /// fails to inflate and is then logged via the lossy UTF-8 fallback in
+    /// [`decode_response_body`]; it is garbled only if a decoder happens to
+    /// accept it.
+    pub fn log_error_response(&self, status: StatusCode, body: &[u8]) {
+        let encoding = match self.inner.content_encoding.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        let decoded = decode_response_body(encoding.as_deref(), body);
+
+        self.write_error_line(json!({
             "timestamp": timestamp(),
             "type": "error_response",
             "status": status.as_u16(),
-            "body": body,
+            "class": classify_status(status).as_str(),
+            "body": self.redaction.redact_body(&decoded),
         }));
     }
 
@@ -87,32 +480,328 @@ impl RequestAttemptLogger {
     }
 
     fn write_json_line(&self, value: Value) {
-        let mut guard = match self.inner.file.lock() {
+        self.inner.response_sink.write_record(&value);
+    }
+
+    /// Routes `log_error`/`log_error_response` records according to the
+    /// `RequestLogger`'s configured [`LogCategory::Error`] rule: dropped if
+    /// disabled, appended to a shared `errors.jsonl` if the category has its
+    /// own destination, or folded into the per-attempt response stream
+    /// otherwise (the historical, `from_env` behavior).
+    fn write_error_line(&self, value: Value) {
+        match &self.inner.error_destination {
+            ErrorDestination::SameAsResponse => self.write_json_line(value),
+            ErrorDestination::Disabled => {}
+            ErrorDestination::Dedicated(sink) => sink.write_record(&value),
+        }
+    }
+}
+
+/// Inflates `body` according to `content_encoding` ("gzip", "deflate", or
+/// "br") before it is logged, falling back to a lossy UTF-8 decode of the raw
+/// bytes if the encoding is unrecognized or decompression fails, so logging
+/// never panics or drops the record over a malformed/unsupported encoding.
+fn decode_response_body(content_encoding: Option<&str>, body: &[u8]) -> String {
+    let decoded = match content_encoding {
+        Some("gzip") => inflate_gzip(body),
+        Some("deflate") => inflate_deflate(body),
+        Some("br") => inflate_brotli(body),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| String::from_utf8_lossy(body).into_owned())
+}
+
+fn inflate_gzip(body: &[u8]) -> Option<String> {
+    let mut out = String::new();
+    GzDecoder::new(body).read_to_string(&mut out).ok()?;
+    Some(out)
+}
+
+/// HTTP `Content-Encoding: deflate` is zlib-wrapped (RFC 1950) in practice —
+/// it's what reqwest's own decoder assumes — so try that first. A handful of
+/// servers send raw RFC 1951 deflate instead, so fall back to that before
+/// giving up and letting the caller fall back to a lossy UTF-8 decode.
+fn inflate_deflate(body: &[u8]) -> Option<String> {
+    let mut out = String::new();
+    if ZlibDecoder::new(body).read_to_string(&mut out).is_ok() {
+        return Some(out);
+    }
+    out.clear();
+    DeflateDecoder::new(body).read_to_string(&mut out).ok()?;
+    Some(out)
+}
+
+fn inflate_brotli(body: &[u8]) -> Option<String> {
+    let mut out = Vec::new();
+    brotli::BrotliDecompress(&mut std::io::Cursor::new(body), &mut out).ok()?;
+    String::from_utf8(out).ok()
+}
+
+fn encode_json_line(value: &Value) -> Option<Vec<u8>> {
+    let mut bytes = match serde_json::to_vec(value) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("request log serialization error: {}", e);
+            return None;
+        }
+    };
+    bytes.push(b'\n');
+    Some(bytes)
+}
+
+/// Rotation policy shared by every `RequestAttemptLogger` created from a given
+/// `RequestLogger`: the byte size at which a response stream is rolled over to a
+/// new segment, and the number of gzip-compressed segments kept on disk.
+#[derive(Clone, Copy, Debug)]
+struct RotationPolicy {
+    max_bytes: u64,
+    max_segments: usize,
+}
+
+impl RotationPolicy {
+    fn from_env() -> Self {
+        let max_bytes = std::env::var(REQUEST_LOG_MAX_BYTES_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        let max_segments = std::env::var(REQUEST_LOG_MAX_SEGMENTS_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_SEGMENTS);
+        Self {
+            max_bytes,
+            max_segments,
+        }
+    }
+}
+
+/// Mutable, lock-guarded rotation state for a single attempt's response stream.
+#[derive(Debug)]
+struct RotatingFile {
+    file: std::fs::File,
+    offset: u64,
+    path: PathBuf,
+    policy: RotationPolicy,
+    segment: u64,
+}
+
+impl RotatingFile {
+    fn write_all(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.offset > 0 && self.offset + bytes.len() as u64 > self.policy.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(bytes)?;
+        self.file.flush()?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.segment += 1;
+        let rotated_path = self
+            .path
+            .with_extension(format!("{}.jsonl", self.segment));
+        std::fs::rename(&self.path, &rotated_path)?;
+
+        compress_and_prune_in_background(rotated_path, self.path.clone(), self.policy);
+
+        let fresh = create_response_file(&self.path)?;
+        self.file = fresh;
+        self.offset = 0;
+        Ok(())
+    }
+}
+
+/// The default [`LogSink`]: a local, size-rotated, gzip-compressed file.
+/// This is the sink every `RequestAttemptLogger` used before sinks became
+/// pluggable, and remains the one `from_env`/`from_config` wire up unless a
+/// remote destination is configured alongside it.
+#[derive(Debug)]
+struct FileSink {
+    state: Mutex<RotatingFile>,
+}
+
+impl LogSink for FileSink {
+    fn write_record(&self, value: &Value) {
+        let Some(bytes) = encode_json_line(value) else {
+            return;
+        };
+        let mut state = match self.state.lock() {
             Ok(guard) => guard,
             Err(poisoned) => poisoned.into_inner(),
         };
-        if let Err(e) = serde_json::to_writer(&mut *guard, &value) {
-            warn!("request log serialization error: {}", e);
+        if let Err(e) = state.write_all(bytes.as_slice()) {
+            warn!("request log write error: {}", e);
+        }
+    }
+
+    fn flush(&self) {
+        let mut state = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Err(e) = state.file.flush() {
+            warn!("request log flush error: {}", e);
+        }
+    }
+}
+
+/// A [`LogSink`] over a single append-only local file, shared across every
+/// attempt in a conversation. Used for the dedicated `errors.jsonl` stream,
+/// which (unlike the per-attempt response stream) isn't rotated.
+#[derive(Debug)]
+struct AppendFileSink {
+    file: Mutex<std::fs::File>,
+}
+
+impl LogSink for AppendFileSink {
+    fn write_record(&self, value: &Value) {
+        let Some(bytes) = encode_json_line(value) else {
             return;
+        };
+        let mut file = match self.file.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Err(e) = file.write_all(bytes.as_slice()).and_then(|()| file.flush()) {
+            warn!("request error log write error: {}", e);
         }
-        if let Err(e) = guard.write_all(b"\n") {
-            warn!("request log write error: {}", e);
+    }
+
+    fn flush(&self) {
+        let mut file = match self.file.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Err(e) = file.flush() {
+            warn!("request error log flush error: {}", e);
+        }
+    }
+}
+
+/// A [`LogSink`] that discards everything written to it, used as the
+/// response-stream sink when [`LogCategory::Response`] is disabled so that
+/// category can be turned off without tearing down the `error_destination`
+/// that `log_error`/`log_error_response` depend on.
+#[derive(Debug)]
+struct NullSink;
+
+impl LogSink for NullSink {
+    fn write_record(&self, _value: &Value) {}
+    fn flush(&self) {}
+}
+
+/// Gzips `rotated_path` into `attempt-NNN-response.N.jsonl.gz` on a background
+/// thread and deletes the oldest compressed segments once more than
+/// `policy.max_segments` remain, so a long-running stream never blocks on
+/// compression and disk usage stays bounded.
+fn compress_and_prune_in_background(
+    rotated_path: PathBuf,
+    response_path: PathBuf,
+    policy: RotationPolicy,
+) {
+    std::thread::spawn(move || {
+        if let Err(e) = gzip_file(&rotated_path) {
+            warn!("failed to gzip rotated request log {:?}: {}", rotated_path, e);
             return;
         }
-        if let Err(e) = guard.flush() {
-            warn!("request log flush error: {}", e);
+        if let Err(e) = prune_old_segments(&response_path, policy.max_segments) {
+            warn!("failed to prune rotated request logs for {:?}: {}", response_path, e);
         }
+    });
+}
+
+fn gzip_file(path: &Path) -> std::io::Result<()> {
+    let data = std::fs::read(path)?;
+
+    let gz_path = PathBuf::from(format!("{}.gz", path.display()));
+    let mut opts = OpenOptions::new();
+    opts.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let gz_file = opts.open(&gz_path)?;
+
+    let mut encoder = GzEncoder::new(gz_file, Compression::default());
+    encoder.write_all(&data)?;
+    encoder.finish()?;
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Deletes the oldest `attempt-NNN-response.N.jsonl.gz` segments for
+/// `response_path`, keeping only the newest `max_segments`.
+fn prune_old_segments(response_path: &Path, max_segments: usize) -> std::io::Result<()> {
+    let dir = response_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let stem = response_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let prefix = format!("{stem}.");
+
+    let mut segments: Vec<(u64, PathBuf)> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?.to_string();
+            let rest = name.strip_prefix(&prefix)?.strip_suffix(".jsonl.gz")?;
+            let segment: u64 = rest.parse().ok()?;
+            Some((segment, path))
+        })
+        .collect();
+
+    if segments.len() <= max_segments {
+        return Ok(());
+    }
+
+    segments.sort_by_key(|(segment, _)| *segment);
+    let to_remove = segments.len() - max_segments;
+    for (_, path) in segments.into_iter().take(to_remove) {
+        std::fs::remove_file(path)?;
     }
+    Ok(())
+}
+
+/// Where `log_error`/`log_error_response` records end up, resolved once per
+/// `RequestLogger` from its [`LogCategory::Error`] rule and shared by every
+/// attempt so errors from the same conversation land in one place.
+#[derive(Clone, Debug)]
+enum ErrorDestination {
+    /// Set by `from_env`, and by `from_config` when the error stream's file
+    /// cannot be opened: errors are folded into the per-attempt response
+    /// stream, as before.
+    SameAsResponse,
+    /// The error category's `enabled` flag is `false`: drop the record.
+    Disabled,
+    /// A conversation-wide sink that every attempt appends error records to.
+    Dedicated(Arc<dyn LogSink>),
 }
 
 #[derive(Debug)]
 struct RequestAttemptLogInner {
-    file: Mutex<std::fs::File>,
+    response_sink: Arc<dyn LogSink>,
+    error_destination: ErrorDestination,
+    /// `content-encoding` observed on `log_response_start`, remembered so a
+    /// later `log_error_response` can inflate the matching raw body.
+    content_encoding: Mutex<Option<String>>,
 }
 
 #[derive(Debug)]
 pub struct RequestLogger {
+    conversation_id: String,
     conversation_dir: PathBuf,
+    policy: RotationPolicy,
+    redaction: Arc<RedactionPolicy>,
+    response_enabled: bool,
+    error_destination: ErrorDestination,
+    remote: Option<RemoteSinkConfig>,
 }
 
 impl RequestLogger {
@@ -126,7 +815,97 @@ impl RequestLogger {
             );
             return None;
         }
-        Some(Arc::new(Self { conversation_dir }))
+        Some(Arc::new(Self {
+            conversation_id: conversation_id.to_string(),
+            conversation_dir,
+            policy: RotationPolicy::from_env(),
+            redaction: Arc::new(RedactionPolicy::default()),
+            response_enabled: true,
+            error_destination: ErrorDestination::SameAsResponse,
+            remote: None,
+        }))
+    }
+
+    /// Builds a `RequestLogger` from a typed, versioned config (see
+    /// [`RequestLoggingConfig`]) instead of the single `CODEX_REQUEST_LOG_DIR`
+    /// on/off switch, so the response stream and the error stream can be
+    /// independently enabled, routed, and optionally mirrored to a remote
+    /// collector via [`RemoteSinkConfig`].
+    pub fn from_config(
+        conversation_id: &ConversationId,
+        base_dir: &Path,
+        config: RequestLoggingConfig,
+    ) -> Option<Arc<Self>> {
+        let config = config.validate()?;
+        let conversation_dir = base_dir.join(conversation_id.to_string());
+        if let Err(e) = std::fs::create_dir_all(&conversation_dir) {
+            warn!(
+                "failed to create request log directory {:?}: {}",
+                conversation_dir, e
+            );
+            return None;
+        }
+
+        let response_rule = config.rule(LogCategory::Response);
+        let error_rule = config.rule(LogCategory::Error);
+        let error_destination = if !error_rule.enabled {
+            ErrorDestination::Disabled
+        } else {
+            let path = error_rule
+                .path
+                .unwrap_or_else(|| conversation_dir.join("errors.jsonl"));
+            match open_append_file(&path) {
+                Ok(file) => {
+                    let file_sink: Arc<dyn LogSink> = Arc::new(AppendFileSink {
+                        file: Mutex::new(file),
+                    });
+                    // Mirror the dedicated errors.jsonl stream to the remote
+                    // collector too, so shipping both JSONL streams (see
+                    // `RemoteSinkConfig`) holds even when errors are routed
+                    // away from the per-attempt response stream.
+                    let sink = match &config.remote {
+                        Some(remote) => Arc::new(CompositeSink::new(vec![
+                            file_sink,
+                            Arc::new(HttpBatchSink::new(
+                                remote.base_url.clone(),
+                                conversation_id.to_string(),
+                                "errors".to_string(),
+                            )),
+                        ])) as Arc<dyn LogSink>,
+                        None => file_sink,
+                    };
+                    ErrorDestination::Dedicated(sink)
+                }
+                Err(e) => {
+                    warn!("failed to open error log {:?}: {}", path, e);
+                    ErrorDestination::SameAsResponse
+                }
+            }
+        };
+
+        Some(Arc::new(Self {
+            conversation_id: conversation_id.to_string(),
+            conversation_dir,
+            policy: RotationPolicy::from_env(),
+            redaction: Arc::new(RedactionPolicy::default()),
+            response_enabled: response_rule.enabled,
+            error_destination,
+            remote: config.remote,
+        }))
+    }
+
+    /// Overrides the default redaction rules, e.g. to add extra JSON
+    /// pointers or enable salted-hash correlation instead of flat masking.
+    pub fn with_redaction_policy(self: Arc<Self>, redaction: RedactionPolicy) -> Arc<Self> {
+        Arc::new(Self {
+            conversation_id: self.conversation_id.clone(),
+            conversation_dir: self.conversation_dir.clone(),
+            policy: self.policy,
+            redaction: Arc::new(redaction),
+            response_enabled: self.response_enabled,
+            error_destination: self.error_destination.clone(),
+            remote: self.remote.clone(),
+        })
     }
 
     pub fn log_request(
@@ -135,6 +914,22 @@ impl RequestLogger {
         url: &str,
         payload: &Value,
     ) -> Option<RequestAttemptLogger> {
+        if !self.response_enabled {
+            // The response category is off, but a dedicated error
+            // destination (or one set up by a future rule) must keep
+            // working independent of it, so still hand back a logger —
+            // just with a no-op response sink instead of bailing out
+            // entirely and taking `log_error`/`log_error_response` with it.
+            return Some(RequestAttemptLogger {
+                inner: Arc::new(RequestAttemptLogInner {
+                    response_sink: Arc::new(NullSink),
+                    error_destination: self.error_destination.clone(),
+                    content_encoding: Mutex::new(None),
+                }),
+                redaction: Arc::clone(&self.redaction),
+            });
+        }
+
         let attempt_id = format!("attempt-{attempt:03}");
         let request_path = self
             .conversation_dir
@@ -143,17 +938,45 @@ impl RequestLogger {
             .conversation_dir
             .join(format!("{attempt_id}-response.jsonl"));
 
-        if let Err(e) = write_request_file(&request_path, attempt, url, payload) {
+        if let Err(e) =
+            write_request_file(&request_path, attempt, url, payload, &self.redaction)
+        {
             warn!("failed to write request log {:?}: {}", request_path, e);
             return None;
         }
 
         match create_response_file(&response_path) {
-            Ok(file) => Some(RequestAttemptLogger {
-                inner: Arc::new(RequestAttemptLogInner {
-                    file: Mutex::new(file),
-                }),
-            }),
+            Ok(file) => {
+                let file_sink: Arc<dyn LogSink> = Arc::new(FileSink {
+                    state: Mutex::new(RotatingFile {
+                        file,
+                        offset: 0,
+                        path: response_path,
+                        policy: self.policy,
+                        segment: 0,
+                    }),
+                });
+                let response_sink: Arc<dyn LogSink> = match &self.remote {
+                    Some(remote) => Arc::new(CompositeSink::new(vec![
+                        file_sink,
+                        Arc::new(HttpBatchSink::new(
+                            remote.base_url.clone(),
+                            self.conversation_id.clone(),
+                            attempt_id.clone(),
+                        )),
+                    ])),
+                    None => file_sink,
+                };
+
+                Some(RequestAttemptLogger {
+                    inner: Arc::new(RequestAttemptLogInner {
+                        response_sink,
+                        error_destination: self.error_destination.clone(),
+                        content_encoding: Mutex::new(None),
+                    }),
+                    redaction: Arc::clone(&self.redaction),
+                })
+            }
             Err(e) => {
                 warn!("failed to prepare response log {:?}: {}", response_path, e);
                 None
@@ -162,11 +985,25 @@ impl RequestLogger {
     }
 }
 
+fn open_append_file(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut opts = OpenOptions::new();
+    opts.create(true).append(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+
+    opts.open(path)
+}
+
 fn write_request_file(
     path: &Path,
     attempt: u64,
     url: &str,
     payload: &Value,
+    redaction: &RedactionPolicy,
 ) -> std::io::Result<()> {
     let mut opts = OpenOptions::new();
     opts.create(true).write(true).truncate(true);
@@ -178,6 +1015,7 @@ fn write_request_file(
     }
 
     let mut file = opts.open(path)?;
+    let payload = redaction.redact_value(payload);
 
     let record = json!({
         "timestamp": timestamp(),
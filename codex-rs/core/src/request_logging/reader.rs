@@ -0,0 +1,247 @@
+//! Reads back the JSONL files written by [`super::RequestAttemptLogger`],
+//! either as a one-shot replay of a completed attempt or as a live "follow"
+//! of one that is still being streamed to.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Default pause between polls when the log file or its next line isn't
+/// available yet.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One decoded line from an `attempt-NNN-response.jsonl` file, mirroring the
+/// `"type"` discriminant written by [`super::RequestAttemptLogger`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogRecord {
+    ResponseStarted {
+        timestamp: String,
+        status: u16,
+        headers: BTreeMap<String, Vec<String>>,
+    },
+    SseEvent {
+        timestamp: String,
+        event: Option<String>,
+        data: String,
+        #[serde(default)]
+        data_json: Option<Value>,
+    },
+    SseClosed {
+        timestamp: String,
+        reason: String,
+    },
+    Error {
+        timestamp: String,
+        message: String,
+    },
+    ErrorResponse {
+        timestamp: String,
+        status: u16,
+        body: Value,
+    },
+    Info {
+        timestamp: String,
+        message: String,
+    },
+}
+
+impl LogRecord {
+    /// Whether this record marks the natural end of an attempt's stream, so
+    /// a [`FollowReader`] can stop polling once it has seen one. An attempt
+    /// can end on a plain `Error` record too (`log_error`/`log_transport_error`
+    /// write one and then the attempt is done), not just `SseClosed`/
+    /// `ErrorResponse`, so all three are terminal.
+    fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            LogRecord::SseClosed { .. } | LogRecord::Error { .. } | LogRecord::ErrorResponse { .. }
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReadRecordError {
+    #[error("failed to read request log: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to decode request log line {line:?}: {source}")]
+    Decode {
+        line: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Tails an `attempt-NNN-response.jsonl` file and yields a typed
+/// [`LogRecord`] per line, tolerating the file not existing yet (the writer
+/// may not have created it) and partial trailing lines (the writer may be
+/// mid-flush) by retrying after [`DEFAULT_POLL_INTERVAL`] instead of erroring.
+/// Also detects the rotation `RequestAttemptLogger` performs once the stream
+/// exceeds its size limit (the old file is renamed away and a fresh one
+/// created at the same path) and transparently reopens, so following a
+/// long-running attempt across a rotation still converges on the terminal
+/// record instead of polling the renamed-then-deleted inode forever.
+/// Stops once a terminal record (`sse_closed`, `error`, or `error_response`)
+/// has been observed, which is what lets a live "follow" converge to
+/// end-of-stream instead of polling a finished attempt forever.
+///
+/// This only works when `error`/`error_response` records are actually
+/// written to *this* response stream, i.e. the logger's error category is
+/// configured as `ErrorDestination::SameAsResponse` (what `from_env` sets
+/// up; `from_config` uses it only if it cannot open `errors.jsonl`). If it's
+/// instead routed to a dedicated, cross-attempt `errors.jsonl`
+/// (`ErrorDestination::Dedicated`), an attempt that ends on an error never
+/// writes a terminal record to its own `attempt-NNN-response.jsonl`, and a
+/// `FollowReader` on that file will poll forever; callers in that
+/// configuration need a separate signal (e.g. also tailing `errors.jsonl`,
+/// or an out-of-band "attempt finished" notification) to know when to stop.
+pub struct FollowReader {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    /// Bytes consumed from the currently open file. Where inode numbers are
+    /// not available, compared against the on-disk length at EOF to detect
+    /// rotation: a fresh file created at the same path by
+    /// `RotatingFile::rotate` starts empty, so it is shorter than what we'd
+    /// already read from the file it replaced until the writer refills it to
+    /// that length.
+    bytes_read: u64,
+    /// Accumulates a line until it ends in `\n`, so a read that lands
+    /// mid-write (the writer hasn't flushed the rest of the line yet) is
+    /// retried instead of being decoded as JSON prematurely.
+    pending_line: String,
+    reached_end: bool,
+    poll_interval: Duration,
+}
+
+impl FollowReader {
+    pub fn new(conversation_dir: &Path, attempt: u64) -> Self {
+        let path = conversation_dir.join(format!("attempt-{attempt:03}-response.jsonl"));
+        Self {
+            path,
+            reader: None,
+            bytes_read: 0,
+            pending_line: String::new(),
+            reached_end: false,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Whether a terminal record has already been yielded; once `true` the
+    /// iterator is exhausted and will not poll again.
+    pub fn reached_end(&self) -> bool {
+        self.reached_end
+    }
+
+    fn open_reader(&mut self) -> bool {
+        if self.reader.is_some() {
+            return true;
+        }
+        match File::open(&self.path) {
+            Ok(file) => {
+                self.reader = Some(BufReader::new(file));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// True once the file at `self.path` is no longer the file the open
+    /// handle reads — the signature of a rotation (rename-away-and-recreate).
+    /// On Unix this compares device and inode numbers; elsewhere it checks
+    /// whether the file at `self.path` is shorter than what we've already
+    /// read, since ordinary appends to the same file only ever grow it.
+    fn path_was_rotated(&self) -> bool {
+        let Ok(on_disk) = std::fs::metadata(&self.path) else {
+            return false;
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if let Some(open) = self.reader.as_ref().and_then(|r| r.get_ref().metadata().ok()) {
+                return on_disk.dev() != open.dev() || on_disk.ino() != open.ino();
+            }
+        }
+        on_disk.len() < self.bytes_read
+    }
+}
+
+impl Iterator for FollowReader {
+    type Item = Result<LogRecord, ReadRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reached_end {
+            return None;
+        }
+
+        loop {
+            if !self.open_reader() {
+                sleep(self.poll_interval);
+                continue;
+            }
+
+            let mut chunk = String::new();
+            let read = self
+                .reader
+                .as_mut()
+                .expect("reader was just opened")
+                .read_line(&mut chunk);
+
+            match read {
+                Ok(0) => {
+                    if self.path_was_rotated() {
+                        // Rotation only ever happens right before writing a
+                        // whole new JSON line, never mid-line, so there is
+                        // no pending partial line to salvage from the old
+                        // segment — just reopen at the fresh file.
+                        self.reader = None;
+                        self.bytes_read = 0;
+                        self.pending_line.clear();
+                        continue;
+                    }
+                    sleep(self.poll_interval);
+                    continue;
+                }
+                Ok(n) => {
+                    self.bytes_read += n as u64;
+                    self.pending_line.push_str(&chunk);
+
+                    if !self.pending_line.ends_with('\n') {
+                        // Partial trailing line: the writer hasn't flushed
+                        // the rest yet. Keep the bytes we have and retry
+                        // rather than handing an incomplete line to serde.
+                        sleep(self.poll_interval);
+                        continue;
+                    }
+
+                    let line = std::mem::take(&mut self.pending_line);
+                    let trimmed = line.trim_end_matches(['\n', '\r']);
+                    if trimmed.is_empty() {
+                        // A bare newline; move on to the next line.
+                        continue;
+                    }
+
+                    return Some(match serde_json::from_str::<LogRecord>(trimmed) {
+                        Ok(record) => {
+                            self.reached_end = record.is_terminal();
+                            Ok(record)
+                        }
+                        Err(source) => Err(ReadRecordError::Decode {
+                            line: trimmed.to_string(),
+                            source,
+                        }),
+                    });
+                }
+                Err(e) => return Some(Err(ReadRecordError::Io(e))),
+            }
+        }
+    }
+}
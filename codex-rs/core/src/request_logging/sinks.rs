@@ -0,0 +1,235 @@
+//! The [`LogSink`] trait lets a `RequestAttemptLogger` write its JSON records
+//! somewhere other than a local file — e.g. [`HttpBatchSink`] ships them to a
+//! remote collector — without the rest of `request_logging` needing to know
+//! which backend is in play.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::Value;
+use tracing::warn;
+
+/// A destination a `RequestAttemptLogger` can append JSON records to. Local
+/// disk (see `FileSink` in the parent module) and network/object-store
+/// backends share this interface so `RequestAttemptLogger` only ever holds
+/// an `Arc<dyn LogSink>`.
+pub trait LogSink: Send + Sync + std::fmt::Debug {
+    /// Appends `value` to the sink. Implementations must not block the
+    /// streaming hot path for long — a backend that talks to the network
+    /// should queue the record and ship it from a background task instead of
+    /// doing I/O inline here.
+    fn write_record(&self, value: &Value);
+
+    /// Drains any buffered records and makes a bounded, best-effort attempt
+    /// to deliver them before returning.
+    fn flush(&self);
+}
+
+/// Fans a record out to every sink in the list, e.g. to keep a local
+/// `FileSink` as a debugging fallback alongside an [`HttpBatchSink`] upload.
+#[derive(Debug)]
+pub struct CompositeSink {
+    sinks: Vec<Arc<dyn LogSink>>,
+}
+
+impl CompositeSink {
+    pub fn new(sinks: Vec<Arc<dyn LogSink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+impl LogSink for CompositeSink {
+    fn write_record(&self, value: &Value) {
+        for sink in &self.sinks {
+            sink.write_record(value);
+        }
+    }
+
+    fn flush(&self) {
+        for sink in &self.sinks {
+            sink.flush();
+        }
+    }
+}
+
+const DEFAULT_BATCH_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_MAX_BATCH: usize = 200;
+const DEFAULT_MAX_RETRIES: u32 = 5;
+/// Bounds how long an inline `flush`/`drop` call waits for the worker thread
+/// to acknowledge a requested drain, so neither can hang indefinitely on a
+/// stalled connection.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tells the background worker to do something other than wait for its next
+/// scheduled tick. Both variants carry an ack channel so the requester can
+/// wait (bounded) for the requested drain to actually happen.
+enum Control {
+    Flush(mpsc::Sender<()>),
+    Shutdown(mpsc::Sender<()>),
+}
+
+/// Ships batches of JSON records to `POST {base_url}/{conversation_id}/{attempt_id}`,
+/// using the conversation/attempt ids as the object path the same way a
+/// local run keys its per-attempt files. `write_record` only ever pushes onto
+/// an in-memory queue; a background thread owns the actual HTTP call (with
+/// retry) so a flaky connection never blocks the caller's streaming hot path.
+/// `flush` and `drop` ask that same worker thread to drain the queue and make
+/// one delivery pass (each batch with the usual retries), waiting for it only
+/// up to a bounded time, rather than doing the upload inline: the
+/// last reference to a sink is typically dropped from within the async
+/// streaming task's own Tokio runtime, and starting a second runtime with
+/// `block_on` from inside one panics, so the final drain has to happen on
+/// the worker's thread instead of the caller's.
+#[derive(Debug)]
+pub struct HttpBatchSink {
+    pending: Arc<Mutex<Vec<Value>>>,
+    control: Mutex<mpsc::Sender<Control>>,
+}
+
+impl HttpBatchSink {
+    pub fn new(base_url: String, conversation_id: String, attempt_id: String) -> Self {
+        let pending: Arc<Mutex<Vec<Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let client = Client::builder()
+            .timeout(DEFAULT_REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!("failed to configure request log upload client: {}", e);
+                Client::new()
+            });
+        let url = format!("{base_url}/{conversation_id}/{attempt_id}");
+        let (control_tx, control_rx) = mpsc::channel();
+
+        let worker_pending = Arc::clone(&pending);
+        std::thread::spawn(move || {
+            run_batch_worker(client, url, worker_pending, control_rx);
+        });
+
+        Self {
+            pending,
+            control: Mutex::new(control_tx),
+        }
+    }
+
+    /// Asks the worker thread to drain `pending` and make one delivery
+    /// pass now, then waits (bounded by [`DEFAULT_REQUEST_TIMEOUT`]) for
+    /// it to finish, so the caller gets a best-effort synchronous flush
+    /// without ever entering a second Tokio runtime itself.
+    fn request_drain(&self, make_control: impl FnOnce(mpsc::Sender<()>) -> Control) {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        let sent = {
+            let control = match self.control.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            control.send(make_control(ack_tx)).is_ok()
+        };
+        if sent {
+            let _ = ack_rx.recv_timeout(DEFAULT_REQUEST_TIMEOUT);
+        }
+    }
+}
+
+impl LogSink for HttpBatchSink {
+    fn write_record(&self, value: &Value) {
+        let mut guard = match self.pending.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.push(value.clone());
+    }
+
+    fn flush(&self) {
+        self.request_drain(Control::Flush);
+    }
+}
+
+impl Drop for HttpBatchSink {
+    fn drop(&mut self) {
+        self.request_drain(Control::Shutdown);
+    }
+}
+
+fn run_batch_worker(
+    client: Client,
+    url: String,
+    pending: Arc<Mutex<Vec<Value>>>,
+    control_rx: mpsc::Receiver<Control>,
+) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            warn!("failed to start request log upload worker: {}", e);
+            return;
+        }
+    };
+
+    // Records that failed to upload are kept here and retried on the next
+    // tick instead of being dropped. The backlog has no size limit, and
+    // whatever is still in it when the final shutdown pass fails is lost.
+    let mut backlog: Vec<Value> = Vec::new();
+    loop {
+        let (shutting_down, ack) = match control_rx.recv_timeout(DEFAULT_BATCH_INTERVAL) {
+            Ok(Control::Flush(ack)) => (false, Some(ack)),
+            Ok(Control::Shutdown(ack)) => (true, Some(ack)),
+            Err(RecvTimeoutError::Timeout) => (false, None),
+            // The sink was dropped without going through `Drop` sending a
+            // `Shutdown` (shouldn't happen in practice) — treat it the same
+            // as a shutdown so the worker doesn't spin forever.
+            Err(RecvTimeoutError::Disconnected) => (true, None),
+        };
+
+        let drained = {
+            let mut guard = match pending.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            std::mem::take(&mut *guard)
+        };
+        backlog.extend(drained);
+
+        while !backlog.is_empty() {
+            let take = backlog.len().min(DEFAULT_MAX_BATCH);
+            let batch: Vec<Value> = backlog.drain(..take).collect();
+            if runtime
+                .block_on(send_batch_with_retry(&client, &url, &batch))
+                .is_err()
+            {
+                backlog.splice(0..0, batch);
+                // Don't hammer a down collector; leave the rest for the next
+                // tick (or, if we're shutting down, give up on this final
+                // pass rather than looping forever).
+                break;
+            }
+        }
+
+        if let Some(ack) = ack {
+            let _ = ack.send(());
+        }
+        if shutting_down {
+            break;
+        }
+    }
+}
+
+async fn send_batch_with_retry(client: &Client, url: &str, batch: &[Value]) -> Result<(), ()> {
+    for attempt in 1..=DEFAULT_MAX_RETRIES {
+        match client.post(url).json(batch).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                warn!("request log upload to {} rejected: {}", url, response.status());
+            }
+            Err(e) => {
+                warn!("request log upload to {} failed: {}", url, e);
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(200 * u64::from(attempt))).await;
+    }
+    Err(())
+}